@@ -0,0 +1,127 @@
+use crate::bellman::pairing::Engine;
+
+use crate::bellman::pairing::ff::{
+    Field
+};
+
+use crate::bellman::{
+    SynthesisError
+};
+
+use crate::bellman::plonk::better_better_cs::cs::{
+    Variable,
+    ConstraintSystem,
+    ArithmeticTerm,
+    MainGateTerm
+};
+
+use super::allocated_num::{
+    AllocatedNum,
+    Num
+};
+
+/// An accumulator of `Variable`s scaled by coefficients plus a constant term, used to
+/// thread round-by-round state through a gadget (e.g. the Rescue permutation) without
+/// allocating a fresh variable at every linear step.
+#[derive(Clone, Debug)]
+pub struct LinearCombination<E: Engine> {
+    value: Option<E::Fr>,
+    constant_term: E::Fr,
+    terms: Vec<(Variable, E::Fr)>
+}
+
+impl<E: Engine> LinearCombination<E> {
+    pub fn zero() -> Self {
+        Self {
+            value: Some(E::Fr::zero()),
+            constant_term: E::Fr::zero(),
+            terms: vec![]
+        }
+    }
+
+    pub fn get_value(&self) -> Option<E::Fr> {
+        self.value
+    }
+
+    pub fn add_assign_constant(&mut self, constant: E::Fr) {
+        if let Some(ref mut value) = self.value {
+            value.add_assign(&constant);
+        }
+
+        self.constant_term.add_assign(&constant);
+    }
+
+    pub fn add_assign_number_with_coeff(&mut self, number: &Num<E>, coeff: E::Fr) {
+        match number {
+            Num::Constant(constant) => {
+                let mut scaled = *constant;
+                scaled.mul_assign(&coeff);
+
+                self.add_assign_constant(scaled);
+            },
+            Num::Variable(var) => {
+                match (self.value, var.get_value()) {
+                    (Some(mut value), Some(to_add)) => {
+                        let mut scaled = to_add;
+                        scaled.mul_assign(&coeff);
+                        value.add_assign(&scaled);
+
+                        self.value = Some(value);
+                    },
+                    _ => {
+                        self.value = None;
+                    }
+                }
+
+                self.terms.push((var.get_variable(), coeff));
+            }
+        }
+    }
+
+    /// Collapses the linear combination into a single `Num`, allocating a fresh
+    /// variable and constraining it to equal the accumulated terms if there are any.
+    pub fn into_num<CS: ConstraintSystem<E>>(self, cs: &mut CS) -> Result<Num<E>, SynthesisError> {
+        if self.terms.is_empty() {
+            return Ok(Num::Constant(self.constant_term));
+        }
+
+        let value = self.value;
+        let collapsed = AllocatedNum::alloc(
+            cs,
+            || value.ok_or(SynthesisError::AssignmentMissing)
+        )?;
+
+        let mut term = MainGateTerm::<E>::new();
+        for (var, coeff) in self.terms.into_iter() {
+            term.add_assign(ArithmeticTerm::from_variable_and_coeff(var, coeff));
+        }
+        term.add_assign(ArithmeticTerm::constant(self.constant_term));
+        term.sub_assign(ArithmeticTerm::from_variable(collapsed.get_variable()));
+
+        cs.allocate_main_gate(term)?;
+
+        Ok(Num::Variable(collapsed))
+    }
+
+    /// Evaluates the linear combination against the witness values currently assigned
+    /// in `cs`, without allocating anything. Returns `None` if any term's variable is
+    /// not yet assigned, which lets gadget authors sanity-check multi-round
+    /// constructions (like the Rescue permutation) during testing.
+    pub fn eval<CS: ConstraintSystem<E>>(&self, cs: &CS) -> Result<Option<E::Fr>, SynthesisError> {
+        let mut result = self.constant_term;
+
+        for (var, coeff) in self.terms.iter() {
+            let value = match cs.get_value(*var) {
+                Ok(value) => value,
+                Err(SynthesisError::AssignmentMissing) => return Ok(None),
+                Err(e) => return Err(e)
+            };
+
+            let mut scaled = value;
+            scaled.mul_assign(coeff);
+            result.add_assign(&scaled);
+        }
+
+        Ok(Some(result))
+    }
+}
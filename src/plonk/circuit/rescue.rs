@@ -34,10 +34,126 @@ use super::linear_combination::{
     LinearCombination
 };
 
+use super::boolean::{Boolean, AllocatedBit};
+
+use super::byte::Byte;
+
 use crate::rescue::*;
 
 use super::custom_rescue_gate::*;
 
+fn constrain_product<E: Engine, CS: ConstraintSystem<E>>(
+    cs: &mut CS,
+    a: &AllocatedNum<E>,
+    b: &AllocatedNum<E>,
+    c: &AllocatedNum<E>,
+) -> Result<(), SynthesisError> {
+    let mut term = MainGateTerm::<E>::new();
+    term.add_assign(ArithmeticTerm::from_variable(a.get_variable()).mul_by_variable(b.get_variable()));
+    term.sub_assign(ArithmeticTerm::from_variable(c.get_variable()));
+
+    cs.allocate_main_gate(term)
+}
+
+// allocates c = a*b and constrains the product with a single plain multiplication gate,
+// for use on constraint systems that do not expose a custom Rescue gate
+fn alloc_product<E: Engine, CS: ConstraintSystem<E>>(
+    cs: &mut CS,
+    a: &AllocatedNum<E>,
+    b: &AllocatedNum<E>,
+) -> Result<AllocatedNum<E>, SynthesisError> {
+    let c = AllocatedNum::alloc(
+        cs,
+        || {
+            let mut result = *a.get_value().get()?;
+            result.mul_assign(b.get_value().get()?);
+
+            Ok(result)
+        }
+    )?;
+
+    constrain_product(cs, a, b, &c)?;
+
+    Ok(c)
+}
+
+// folds a single bit, scaled by `coeff`, into a running linear combination being
+// built out of `ArithmeticTerm`s
+fn add_boolean_with_coeff<E: Engine>(term: &mut MainGateTerm<E>, bit: &Boolean, coeff: E::Fr) {
+    match bit {
+        Boolean::Constant(b) => {
+            if *b {
+                term.add_assign(ArithmeticTerm::constant(coeff));
+            }
+        },
+        Boolean::Is(ref bit) => {
+            term.add_assign(ArithmeticTerm::from_variable_and_coeff(bit.get_variable(), coeff));
+        },
+        Boolean::Not(ref bit) => {
+            term.add_assign(ArithmeticTerm::constant(coeff));
+            term.sub_assign(ArithmeticTerm::from_variable_and_coeff(bit.get_variable(), coeff));
+        }
+    }
+}
+
+// packs a single chunk of at most `Fr::CAPACITY` bits (little-endian) into one
+// allocated field element, the way a multipack gadget accumulates a `LinearCombination`
+fn pack_bits_into_num<E: Engine, CS: ConstraintSystem<E>>(
+    cs: &mut CS,
+    bits: &[Boolean],
+) -> Result<Num<E>, SynthesisError> {
+    assert!(bits.len() <= E::Fr::CAPACITY as usize);
+
+    let mut coeff = E::Fr::one();
+    let mut value = Some(E::Fr::zero());
+    let mut term = MainGateTerm::<E>::new();
+
+    for bit in bits.iter() {
+        match (bit.get_value(), value) {
+            (Some(b), Some(mut acc)) => {
+                if b {
+                    acc.add_assign(&coeff);
+                }
+                value = Some(acc);
+            },
+            _ => { value = None; }
+        }
+
+        add_boolean_with_coeff(&mut term, bit, coeff);
+
+        coeff.double();
+    }
+
+    let packed = AllocatedNum::alloc(cs, || value.ok_or(SynthesisError::AssignmentMissing))?;
+    term.sub_assign(ArithmeticTerm::from_variable(packed.get_variable()));
+    cs.allocate_main_gate(term)?;
+
+    Ok(Num::Variable(packed))
+}
+
+// splits `bits` into `Fr::CAPACITY`-sized chunks and packs each one into a `Num`,
+// appending a single `true` marker bit first so messages of differing length can
+// never pack to the same sequence of field elements
+fn pack_bits_with_padding<E: Engine, CS: ConstraintSystem<E>>(
+    cs: &mut CS,
+    bits: &[Boolean],
+) -> Result<Vec<Num<E>>, SynthesisError> {
+    let capacity = E::Fr::CAPACITY as usize;
+
+    let mut bits = bits.to_vec();
+    bits.push(Boolean::constant(true));
+
+    let padded_len = ((bits.len() + capacity - 1) / capacity) * capacity;
+    bits.resize(padded_len, Boolean::constant(false));
+
+    let mut packed = Vec::with_capacity(padded_len / capacity);
+    for chunk in bits.chunks(capacity) {
+        packed.push(pack_bits_into_num(cs, chunk)?);
+    }
+
+    Ok(packed)
+}
+
 pub trait PlonkCsSBox<E: Engine>: SBox<E> {
     const SHOULD_APPLY_FORWARD: bool;
     fn apply_constraints<CS: ConstraintSystem<E>>(&self, cs: &mut CS, element: &Num<E>, force_no_custom_gates: bool) -> Result<Num<E>, SynthesisError>;
@@ -59,22 +175,22 @@ impl<E: Engine> PlonkCsSBox<E> for QuinticSBox<E> {
             return self.apply_custom_gate(cs, el);
         }
 
-        unimplemented!()
+        self.apply_generic_gate(cs, el)
     }
 
     fn apply_constraints_in_reverse<CS: ConstraintSystem<E>>(
-        &self, 
+        &self,
         cs: &mut CS,
         el: &Num<E>,
         force_no_custom_gates: bool
-    ) -> Result<Num<E>, SynthesisError> {     
+    ) -> Result<Num<E>, SynthesisError> {
         unimplemented!("Making 5th power can only be used in straight order")
     }
 }
 
 impl<E: Engine> QuinticSBox<E> {
     fn apply_custom_gate<CS: ConstraintSystem<E>>(
-        &self, 
+        &self,
         cs: &mut CS,
         el: &Num<E>,
     ) -> Result<Num<E>, SynthesisError> {
@@ -95,6 +211,32 @@ impl<E: Engine> QuinticSBox<E> {
             }
         }
     }
+
+    // same x^5 computation as `apply_custom_gate`, but expressed with plain
+    // multiplication gates so it works on any width-3 main gate without custom gate support
+    fn apply_generic_gate<CS: ConstraintSystem<E>>(
+        &self,
+        cs: &mut CS,
+        el: &Num<E>,
+    ) -> Result<Num<E>, SynthesisError> {
+        match el {
+            Num::Constant(constant) => {
+                let mut result = *constant;
+                result.square();
+                result.square();
+                result.mul_assign(constant);
+
+                Ok(Num::Constant(result))
+            },
+            Num::Variable(el) => {
+                let x2 = alloc_product(cs, el, el)?;
+                let x4 = alloc_product(cs, &x2, &x2)?;
+                let x5 = alloc_product(cs, &x4, el)?;
+
+                Ok(Num::Variable(x5))
+            }
+        }
+    }
 }
 
 impl<E: Engine> PlonkCsSBox<E> for PowerSBox<E> {
@@ -111,15 +253,15 @@ impl<E: Engine> PlonkCsSBox<E> for PowerSBox<E> {
             return self.apply_custom_gate(cs, el);
         }
 
-        unimplemented!()
+        self.apply_generic_gate(cs, el)
     }
 
     fn apply_constraints<CS: ConstraintSystem<E>>(
-        &self, 
+        &self,
         cs: &mut CS,
         el: &Num<E>,
         force_no_custom_gates: bool
-    ) -> Result<Num<E>, SynthesisError> {     
+    ) -> Result<Num<E>, SynthesisError> {
         unimplemented!("Making inverse of 5th power can only be used in backward mode")
     }
 }
@@ -156,6 +298,39 @@ impl<E: Engine> PowerSBox<E> {
             }
         }
     }
+
+    // allocates the witnessed `out = el^power` and enforces `out^5 == el` with three
+    // plain multiplication gates, mirroring `QuinticSBox::apply_generic_gate`
+    fn apply_generic_gate<CS: ConstraintSystem<E>>(
+        &self,
+        cs: &mut CS,
+        el: &Num<E>,
+    ) -> Result<Num<E>, SynthesisError> {
+        match el {
+            Num::Constant(constant) => {
+                let result = constant.pow(&self.power);
+
+                Ok(Num::Constant(result))
+            },
+            Num::Variable(el) => {
+                let out = AllocatedNum::<E>::alloc(
+                    cs,
+                    || {
+                        let base = *el.get_value().get()?;
+                        let result = base.pow(&self.power);
+
+                        Ok(result)
+                    }
+                )?;
+
+                let y2 = alloc_product(cs, &out, &out)?;
+                let y4 = alloc_product(cs, &y2, &y2)?;
+                constrain_product(cs, &y4, &out, el)?;
+
+                Ok(Num::Variable(out))
+            }
+        }
+    }
 }
 
 
@@ -324,6 +499,47 @@ impl<E: RescueEngine> StatefulRescueGadget<E>
         Ok(())
     }
 
+    // packs a bit-oriented message into field elements and absorbs them, so that
+    // byte/bit preimages (e.g. SHA/BLAKE-style messages) don't need to be hand-packed
+    // into `AllocatedNum`s by the caller
+    pub fn absorb_bits<CS: ConstraintSystem<E>>(
+        &mut self,
+        cs: &mut CS,
+        bits: &[Boolean],
+        params: &E::Params
+    ) -> Result<(), SynthesisError> {
+        let mut packed = pack_bits_with_padding(cs, bits)?;
+
+        // like `absorb`, pad with constant ones up to a whole number of `rate`-sized blocks
+        let rate = params.rate() as usize;
+        let padded_len = ((packed.len() + rate - 1) / rate) * rate;
+        packed.resize(padded_len, Num::Constant(E::Fr::one()));
+
+        for num in packed.into_iter() {
+            self.absorb_single_value(cs, num, params)?;
+        }
+
+        Ok(())
+    }
+
+    pub fn absorb_bytes<CS: ConstraintSystem<E>>(
+        &mut self,
+        cs: &mut CS,
+        bytes: &[Byte<E>],
+        params: &E::Params
+    ) -> Result<(), SynthesisError> {
+        // `Byte::into_bits_le()` yields that byte's own bits LSB-first; concatenating
+        // byte 0's bits before byte 1's then produces one little-endian bit sequence
+        // across the *whole* message (byte 0 occupies the low end), matching what
+        // `pack_bits_into_num` assumes when it weights `bits[0]` by `2^0`
+        let mut bits = Vec::with_capacity(bytes.len() * 8);
+        for byte in bytes.iter() {
+            bits.extend(byte.into_bits_le());
+        }
+
+        self.absorb_bits(cs, &bits, params)
+    }
+
     pub fn squeeze_out_single<CS: ConstraintSystem<E>>(
         &mut self,
         cs: &mut CS,
@@ -363,6 +579,285 @@ impl<E: RescueEngine> StatefulRescueGadget<E>
             }
         }
     }
+
+    /// Interleaves absorb and squeeze on the same state: adds `input` (exactly `rate`
+    /// lanes) into the first `rate` state lanes, runs a single permutation, and returns
+    /// the new `rate` lanes (input still buffered from `absorb`/`absorb_bits` is first
+    /// flushed with a permutation of its own). Unlike `absorb`/`squeeze_out_single`, a single call both
+    /// consumes fresh input and produces fresh output, which is what a stream cipher or
+    /// in-circuit PRF built on Rescue needs.
+    pub fn duplex<CS: ConstraintSystem<E>>(
+        &mut self,
+        cs: &mut CS,
+        input: &[Num<E>],
+        params: &E::Params
+    ) -> Result<Vec<LinearCombination<E>>, SynthesisError> {
+        let rate = params.rate() as usize;
+        assert_eq!(input.len(), rate, "duplex() must be called with exactly `rate` elements");
+
+        // flush any buffered `absorb`/`absorb_bits` input first, the same way
+        // `squeeze_out_single` does, so a pending key/associated-data block isn't
+        // silently dropped when the caller switches over to duplexing
+        if let RescueOpMode::AccumulatingToAbsorb(ref into) = self.mode {
+            if !into.is_empty() {
+                assert_eq!(into.len(), rate, "padding was necessary!");
+                let into = into.clone();
+
+                for i in 0..rate {
+                    self.internal_state[i].add_assign_number_with_coeff(&into[i], E::Fr::one());
+                }
+
+                self.internal_state = Self::rescue_mimc_over_lcs(
+                    cs,
+                    &self.internal_state,
+                    &params
+                )?;
+            }
+        }
+
+        for i in 0..rate {
+            self.internal_state[i].add_assign_number_with_coeff(&input[i], E::Fr::one());
+        }
+
+        self.internal_state = Self::rescue_mimc_over_lcs(
+            cs,
+            &self.internal_state,
+            &params
+        )?;
+
+        let output = self.internal_state[0..rate].to_vec();
+
+        // the caller already received the whole rate-wide output above, so there is
+        // nothing left to hand out from a subsequent `squeeze_out_single()` call; reuse
+        // `SqueezedInto` (empty) so that call fails the same way it does once a regular
+        // squeeze is depleted, instead of a distinct `Duplexing` mode
+        self.mode = RescueOpMode::SqueezedInto(vec![]);
+
+        Ok(output)
+    }
+
+    /// Encrypts a single `rate`-wide plaintext block: derives a `rate`-wide keystream by
+    /// duplexing in a zero block, adds it to the plaintext to get the ciphertext, then
+    /// duplexes the ciphertext itself back into the state (SpongeWrap-style) so that the
+    /// resulting internal state lanes are a genuine authentication tag over the
+    /// ciphertext, not just a function of whatever state preceded this call.
+    pub fn encrypt<CS: ConstraintSystem<E>>(
+        &mut self,
+        cs: &mut CS,
+        plaintext: &[Num<E>],
+        params: &E::Params
+    ) -> Result<(Vec<LinearCombination<E>>, Vec<LinearCombination<E>>), SynthesisError> {
+        let rate = params.rate() as usize;
+        assert_eq!(plaintext.len(), rate, "encrypt() operates on one rate-sized block at a time");
+
+        let zeroes = vec![Num::Constant(E::Fr::zero()); rate];
+        let keystream = self.duplex(cs, &zeroes, params)?;
+
+        let mut ciphertext = Vec::with_capacity(rate);
+        let mut ciphertext_nums = Vec::with_capacity(rate);
+        for (keystream_lane, plaintext_lane) in keystream.into_iter().zip(plaintext.iter()) {
+            let mut lane = keystream_lane;
+            lane.add_assign_number_with_coeff(plaintext_lane, E::Fr::one());
+
+            ciphertext_nums.push(lane.clone().into_num(cs)?);
+            ciphertext.push(lane);
+        }
+
+        // bind the tag to the actual ciphertext instead of leaving it a function of the
+        // pre-encryption state alone
+        let _ = self.duplex(cs, &ciphertext_nums, params)?;
+        let tag = self.internal_state.clone();
+
+        Ok((ciphertext, tag))
+    }
+
+    /// Decrypts a single `rate`-wide ciphertext block produced by `encrypt`, recovering
+    /// the keystream the same way and subtracting it back out, then duplexing the same
+    /// ciphertext back into the state so the returned tag can be checked against the one
+    /// `encrypt` produced.
+    pub fn decrypt<CS: ConstraintSystem<E>>(
+        &mut self,
+        cs: &mut CS,
+        ciphertext: &[Num<E>],
+        params: &E::Params
+    ) -> Result<(Vec<LinearCombination<E>>, Vec<LinearCombination<E>>), SynthesisError> {
+        let rate = params.rate() as usize;
+        assert_eq!(ciphertext.len(), rate, "decrypt() operates on one rate-sized block at a time");
+
+        let zeroes = vec![Num::Constant(E::Fr::zero()); rate];
+        let keystream = self.duplex(cs, &zeroes, params)?;
+
+        let mut minus_one = E::Fr::one();
+        minus_one.negate();
+
+        let mut plaintext = Vec::with_capacity(rate);
+        for (keystream_lane, ciphertext_lane) in keystream.into_iter().zip(ciphertext.iter()) {
+            let keystream_num = keystream_lane.into_num(cs)?;
+
+            let mut lane = LinearCombination::zero();
+            lane.add_assign_number_with_coeff(ciphertext_lane, E::Fr::one());
+            lane.add_assign_number_with_coeff(&keystream_num, minus_one);
+
+            plaintext.push(lane);
+        }
+
+        // same ciphertext-binding step as `encrypt`, so a tampered ciphertext lane
+        // produces a tag that no longer matches the one `encrypt` returned
+        let _ = self.duplex(cs, ciphertext, params)?;
+        let tag = self.internal_state.clone();
+
+        Ok((plaintext, tag))
+    }
+}
+
+// decomposes `value` into `n` little-endian bits, each individually constrained to be
+// boolean, and enforces the reconstruction `sum(b_i * 2^i) == value`. The decomposition
+// itself is what range-checks `value` into `[0, 2^n)`.
+fn into_bits_le<E: Engine, CS: ConstraintSystem<E>>(
+    cs: &mut CS,
+    value: &AllocatedNum<E>,
+    n: usize,
+) -> Result<Vec<Boolean>, SynthesisError> {
+    let witness_bits = match value.get_value() {
+        Some(value) => {
+            let mut bits: Vec<bool> = BitIterator::new(value.into_repr()).collect();
+            bits.reverse();
+            bits.truncate(n);
+            bits.into_iter().map(Some).collect()
+        },
+        None => vec![None; n]
+    };
+
+    let mut allocated_bits = Vec::with_capacity(n);
+    let mut term = MainGateTerm::<E>::new();
+    let mut coeff = E::Fr::one();
+
+    for bit in witness_bits.into_iter() {
+        let allocated_bit = AllocatedBit::alloc(cs, bit)?;
+        term.add_assign(ArithmeticTerm::from_variable_and_coeff(allocated_bit.get_variable(), coeff));
+        allocated_bits.push(Boolean::from(allocated_bit));
+
+        coeff.double();
+    }
+
+    term.sub_assign(ArithmeticTerm::from_variable(value.get_variable()));
+    cs.allocate_main_gate(term)?;
+
+    Ok(allocated_bits)
+}
+
+/// Ordering and bounded-range primitives over `AllocatedNum`, built on top of the same
+/// bit-decomposition technique used for range-checking.
+pub struct ComparisonGadget<E: Engine> {
+    _marker: std::marker::PhantomData<E>
+}
+
+impl<E: Engine> ComparisonGadget<E> {
+    // range-checks `a` and `b` into `n` bits each, then computes `delta = a - b + 2^n`,
+    // which lies in `[1, 2^{n+1})` iff both inputs are in range, and returns bit `n` of
+    // `delta`'s `(n+1)`-bit decomposition as the "a >= b" flag
+    fn greater_than_or_equal_flag<CS: ConstraintSystem<E>>(
+        cs: &mut CS,
+        a: &AllocatedNum<E>,
+        b: &AllocatedNum<E>,
+        n: usize
+    ) -> Result<Boolean, SynthesisError> {
+        let _ = into_bits_le(cs, a, n)?;
+        let _ = into_bits_le(cs, b, n)?;
+
+        let mut two_n = E::Fr::one();
+        for _ in 0..n {
+            two_n.double();
+        }
+
+        let delta = AllocatedNum::alloc(
+            cs,
+            || {
+                let mut delta = *a.get_value().get()?;
+                delta.sub_assign(b.get_value().get()?);
+                delta.add_assign(&two_n);
+
+                Ok(delta)
+            }
+        )?;
+
+        let mut term = MainGateTerm::<E>::new();
+        term.add_assign(ArithmeticTerm::from_variable(delta.get_variable()));
+        term.sub_assign(ArithmeticTerm::from_variable(a.get_variable()));
+        term.add_assign(ArithmeticTerm::from_variable(b.get_variable()));
+        term.sub_assign(ArithmeticTerm::constant(two_n));
+        cs.allocate_main_gate(term)?;
+
+        let delta_bits = into_bits_le(cs, &delta, n + 1)?;
+
+        Ok(delta_bits[n].clone())
+    }
+
+    pub fn greater_than_or_equal<CS: ConstraintSystem<E>>(
+        cs: &mut CS,
+        a: &AllocatedNum<E>,
+        b: &AllocatedNum<E>,
+        n: usize
+    ) -> Result<Boolean, SynthesisError> {
+        Self::greater_than_or_equal_flag(cs, a, b, n)
+    }
+
+    pub fn less_than_or_equal<CS: ConstraintSystem<E>>(
+        cs: &mut CS,
+        a: &AllocatedNum<E>,
+        b: &AllocatedNum<E>,
+        n: usize
+    ) -> Result<Boolean, SynthesisError> {
+        Self::greater_than_or_equal_flag(cs, b, a, n)
+    }
+
+    pub fn greater_than<CS: ConstraintSystem<E>>(
+        cs: &mut CS,
+        a: &AllocatedNum<E>,
+        b: &AllocatedNum<E>,
+        n: usize
+    ) -> Result<Boolean, SynthesisError> {
+        Ok(Self::greater_than_or_equal_flag(cs, b, a, n)?.not())
+    }
+
+    pub fn less_than<CS: ConstraintSystem<E>>(
+        cs: &mut CS,
+        a: &AllocatedNum<E>,
+        b: &AllocatedNum<E>,
+        n: usize
+    ) -> Result<Boolean, SynthesisError> {
+        Ok(Self::greater_than_or_equal_flag(cs, a, b, n)?.not())
+    }
+
+    /// Enforces `a == b`, or the chosen strict/non-strict ordering between `a` and `b`,
+    /// over an `n`-bit domain. Both operands must be provably in `[0, 2^n)`, which this
+    /// call itself establishes via range-checking.
+    pub fn enforce_cmp<CS: ConstraintSystem<E>>(
+        cs: &mut CS,
+        a: &AllocatedNum<E>,
+        b: &AllocatedNum<E>,
+        n: usize,
+        ordering: std::cmp::Ordering,
+        or_equal: bool
+    ) -> Result<(), SynthesisError> {
+        // route every ordering, including `Equal`, through the same range-checked
+        // `n`-bit path so `a`/`b` being provably in `[0, 2^n)` really is an invariant
+        // of this call, not just of the strict/non-strict comparisons
+        let flag = match ordering {
+            std::cmp::Ordering::Less if !or_equal => Self::less_than(cs, a, b, n)?,
+            std::cmp::Ordering::Less => Self::less_than_or_equal(cs, a, b, n)?,
+            std::cmp::Ordering::Greater if !or_equal => Self::greater_than(cs, a, b, n)?,
+            std::cmp::Ordering::Greater => Self::greater_than_or_equal(cs, a, b, n)?,
+            std::cmp::Ordering::Equal => {
+                let ge = Self::greater_than_or_equal(cs, a, b, n)?;
+                let le = Self::less_than_or_equal(cs, a, b, n)?;
+
+                Boolean::and(cs, &ge, &le)?
+            }
+        };
+
+        Boolean::enforce_equal(cs, &flag, &Boolean::constant(true))
+    }
 }
 
 #[cfg(test)]
@@ -389,6 +884,18 @@ mod test {
         const CAN_ACCESS_NEXT_TRACE_STEP: bool = true;
     }
 
+    // drives `apply_generic_gate` (the plain-multiplication-gate fallback) instead of
+    // `apply_custom_gate`
+    struct Width4WithoutCustomGates;
+
+    impl<E: Engine> PlonkConstraintSystemParams<E> for Width4WithoutCustomGates {
+        const STATE_WIDTH: usize =  4;
+        const WITNESS_WIDTH: usize = 0;
+        const HAS_WITNESS_POLYNOMIALS: bool = false;
+        const HAS_CUSTOM_GATES: bool = false;
+        const CAN_ACCESS_NEXT_TRACE_STEP: bool = true;
+    }
+
     #[test]
     fn test_rescue_hash_plonk_gadget() {
         use crate::rescue::bn256::*;
@@ -428,6 +935,7 @@ mod test {
             ).unwrap();
 
             assert_eq!(res_0.get_value().unwrap(), expected[0]);
+            assert_eq!(res_0.eval(&cs).unwrap().unwrap(), expected[0]);
             println!("Rescue stateful hash of {} elements taken {} constraints", input.len(), cs.n);
 
             let res_1 = rescue_gadget.squeeze_out_single(
@@ -450,4 +958,242 @@ mod test {
             assert!(cs.is_satisfied());
         }
     }
+
+    #[test]
+    fn test_rescue_hash_plonk_gadget_without_custom_gates() {
+        use crate::rescue::bn256::*;
+        let mut rng = XorShiftRng::from_seed([0x3dbe6259, 0x8d313d76, 0x3237db17, 0xe5bc0654]);
+        let params = Bn256RescueParams::new_checked_2_into_1();
+        let input: Vec<Fr> = (0..(params.rate())).map(|_| rng.gen()).collect();
+        let expected = rescue::rescue_hash::<Bn256>(&params, &input[..]);
+
+        let mut cs = TrivialAssembly::<Bn256,
+            Width4WithoutCustomGates,
+            Width4MainGateWithDNextEquation
+        >::new();
+
+        let input_words: Vec<AllocatedNum<Bn256>> = input.iter().map(|b| {
+            AllocatedNum::alloc(
+                &mut cs,
+                || {
+                    Ok(*b)
+                }).unwrap()
+        }).collect();
+
+        let mut rescue_gadget = StatefulRescueGadget::<Bn256>::new(
+            &params
+        );
+
+        rescue_gadget.absorb(
+            &mut cs,
+            &input_words,
+            &params
+        ).unwrap();
+
+        let res_0 = rescue_gadget.squeeze_out_single(
+            &mut cs,
+            &params
+        ).unwrap();
+
+        assert_eq!(res_0.get_value().unwrap(), expected[0]);
+        println!("Rescue stateful hash without custom gates of {} elements taken {} constraints", input.len(), cs.n);
+
+        assert!(cs.is_satisfied());
+    }
+
+    #[test]
+    fn test_rescue_absorb_bits_plonk_gadget() {
+        use crate::rescue::bn256::*;
+
+        let params = Bn256RescueParams::new_checked_2_into_1();
+
+        let mut cs = TrivialAssembly::<Bn256,
+            Width4WithCustomGates,
+            Width4MainGateWithDNextEquation
+        >::new();
+
+        // includes both `0` and `1` bits so a broken witness accumulator for
+        // the packed value (as opposed to the constraint, which always summed
+        // correctly) would be caught
+        let bit_values = vec![true, true, false, true];
+        let bits: Vec<Boolean> = bit_values.iter().map(|&b| {
+            Boolean::from(AllocatedBit::alloc(&mut cs, Some(b)).unwrap())
+        }).collect();
+
+        let mut rescue_gadget = StatefulRescueGadget::<Bn256>::new(&params);
+
+        rescue_gadget.absorb_bits(&mut cs, &bits, &params).unwrap();
+
+        let res = rescue_gadget.squeeze_out_single(&mut cs, &params).unwrap();
+
+        // mirror `absorb_bits`'s own padding scheme (marker bit + zero-pad to
+        // `Fr::CAPACITY`) to compute the single field element it should have packed
+        let mut padded_bits = bit_values.clone();
+        padded_bits.push(true);
+        padded_bits.resize(Fr::CAPACITY as usize, false);
+
+        let mut packed = Fr::zero();
+        let mut coeff = Fr::one();
+        for b in padded_bits.into_iter() {
+            if b {
+                packed.add_assign(&coeff);
+            }
+            coeff.double();
+        }
+
+        let expected = rescue::rescue_hash::<Bn256>(&params, &[packed]);
+
+        assert_eq!(res.get_value().unwrap(), expected[0]);
+        assert!(cs.is_satisfied());
+    }
+
+    #[test]
+    fn test_rescue_absorb_bytes_plonk_gadget() {
+        use crate::rescue::bn256::*;
+
+        let params = Bn256RescueParams::new_checked_2_into_1();
+
+        let mut cs = TrivialAssembly::<Bn256,
+            Width4WithCustomGates,
+            Width4MainGateWithDNextEquation
+        >::new();
+
+        let byte_values: Vec<u8> = vec![0b1011_0010, 0b0000_0001];
+        let bytes: Vec<Byte<Bn256>> = byte_values.iter().map(|&v| {
+            Byte::from_u8_witness(&mut cs, Some(v)).unwrap()
+        }).collect();
+
+        let mut rescue_gadget = StatefulRescueGadget::<Bn256>::new(&params);
+
+        rescue_gadget.absorb_bytes(&mut cs, &bytes, &params).unwrap();
+
+        let res = rescue_gadget.squeeze_out_single(&mut cs, &params).unwrap();
+
+        // byte 0's bits (LSB-first) occupy the low end of the packed field element,
+        // byte 1's bits sit right above them - i.e. little-endian across the whole
+        // byte sequence, not reversed within each byte
+        let mut padded_bits: Vec<bool> = Vec::with_capacity(byte_values.len() * 8);
+        for byte in byte_values.iter() {
+            for i in 0..8 {
+                padded_bits.push((byte >> i) & 1 == 1);
+            }
+        }
+        padded_bits.push(true);
+        padded_bits.resize(Fr::CAPACITY as usize, false);
+
+        let mut packed = Fr::zero();
+        let mut coeff = Fr::one();
+        for b in padded_bits.into_iter() {
+            if b {
+                packed.add_assign(&coeff);
+            }
+            coeff.double();
+        }
+
+        let expected = rescue::rescue_hash::<Bn256>(&params, &[packed]);
+
+        assert_eq!(res.get_value().unwrap(), expected[0]);
+        assert!(cs.is_satisfied());
+    }
+
+    #[test]
+    fn test_comparison_gadget() {
+        let mut cs = TrivialAssembly::<Bn256,
+            Width4WithCustomGates,
+            Width4MainGateWithDNextEquation
+        >::new();
+
+        let n = 8usize;
+
+        let a = AllocatedNum::alloc(&mut cs, || Ok(Fr::from_str("17").unwrap())).unwrap();
+        let b = AllocatedNum::alloc(&mut cs, || Ok(Fr::from_str("42").unwrap())).unwrap();
+
+        let lt = ComparisonGadget::less_than(&mut cs, &a, &b, n).unwrap();
+        assert_eq!(lt.get_value().unwrap(), true);
+
+        let gt = ComparisonGadget::greater_than(&mut cs, &a, &b, n).unwrap();
+        assert_eq!(gt.get_value().unwrap(), false);
+
+        let le = ComparisonGadget::less_than_or_equal(&mut cs, &a, &a, n).unwrap();
+        assert_eq!(le.get_value().unwrap(), true);
+
+        let ge = ComparisonGadget::greater_than_or_equal(&mut cs, &b, &a, n).unwrap();
+        assert_eq!(ge.get_value().unwrap(), true);
+
+        ComparisonGadget::enforce_cmp(&mut cs, &a, &b, n, std::cmp::Ordering::Less, false).unwrap();
+
+        assert!(cs.is_satisfied());
+    }
+
+    #[test]
+    fn test_comparison_gadget_rejects_out_of_range_operand() {
+        let mut cs = TrivialAssembly::<Bn256,
+            Width4WithCustomGates,
+            Width4MainGateWithDNextEquation
+        >::new();
+
+        let n = 8usize;
+
+        // out of the claimed `[0, 2^8)` range: the range-check reconstruction
+        // constraint must fail to be satisfied
+        let a = AllocatedNum::alloc(&mut cs, || Ok(Fr::from_str("300").unwrap())).unwrap();
+        let b = AllocatedNum::alloc(&mut cs, || Ok(Fr::from_str("42").unwrap())).unwrap();
+
+        let _ = ComparisonGadget::less_than(&mut cs, &a, &b, n).unwrap();
+
+        assert!(!cs.is_satisfied());
+    }
+
+    #[test]
+    fn test_rescue_duplex_encrypt_decrypt_plonk_gadget() {
+        use crate::rescue::bn256::*;
+
+        let mut rng = XorShiftRng::from_seed([0x3dbe6259, 0x8d313d76, 0x3237db17, 0xe5bc0654]);
+        let params = Bn256RescueParams::new_checked_2_into_1();
+        let rate = params.rate() as usize;
+
+        let mut cs = TrivialAssembly::<Bn256,
+            Width4WithCustomGates,
+            Width4MainGateWithDNextEquation
+        >::new();
+
+        let plaintext: Vec<Fr> = (0..rate).map(|_| rng.gen()).collect();
+        let plaintext_nums: Vec<Num<Bn256>> = plaintext.iter().map(|p| {
+            Num::Variable(AllocatedNum::alloc(&mut cs, || Ok(*p)).unwrap())
+        }).collect();
+
+        let mut sender = StatefulRescueGadget::<Bn256>::new(&params);
+        let (ciphertext, tag_encrypt) = sender.encrypt(&mut cs, &plaintext_nums, &params).unwrap();
+
+        let ciphertext_nums: Vec<Num<Bn256>> = ciphertext.iter().cloned().map(|c| {
+            Num::Variable(AllocatedNum::alloc(&mut cs, || c.get_value().get().map(|v| *v)).unwrap())
+        }).collect();
+
+        let mut receiver = StatefulRescueGadget::<Bn256>::new(&params);
+        let (recovered, tag_decrypt) = receiver.decrypt(&mut cs, &ciphertext_nums, &params).unwrap();
+
+        for (p, r) in plaintext.iter().zip(recovered.iter()) {
+            assert_eq!(*p, r.get_value().unwrap());
+        }
+
+        // the tag must depend on the ciphertext: sender and receiver, absorbing the
+        // same ciphertext, must agree
+        for (t0, t1) in tag_encrypt.iter().zip(tag_decrypt.iter()) {
+            assert_eq!(t0.get_value().unwrap(), t1.get_value().unwrap());
+        }
+
+        // flipping a ciphertext lane must change the tag, or it would not authenticate anything
+        let mut tampered = StatefulRescueGadget::<Bn256>::new(&params);
+        let mut tampered_ciphertext_nums = ciphertext_nums.clone();
+        tampered_ciphertext_nums[0] = Num::Variable(AllocatedNum::alloc(&mut cs, || {
+            let mut v = *ciphertext[0].get_value().get()?;
+            v.add_assign(&Fr::one());
+            Ok(v)
+        }).unwrap());
+        let (_, tampered_tag) = tampered.decrypt(&mut cs, &tampered_ciphertext_nums, &params).unwrap();
+
+        assert!(tampered_tag[0].get_value().unwrap() != tag_encrypt[0].get_value().unwrap());
+
+        assert!(cs.is_satisfied());
+    }
 }
\ No newline at end of file